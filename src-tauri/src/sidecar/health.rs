@@ -0,0 +1,158 @@
+//! Pure state machines used by the sidecar monitor: liveness-probe
+//! tracking and restart backoff. Kept free of I/O so the transition logic
+//! can be unit tested without spawning real processes.
+
+use std::time::Duration;
+
+use crate::sidecar::{RESTART_BACKOFF_MAX, RESTART_BACKOFF_MIN};
+
+/// Outcome of feeding one liveness probe (`backend_alive()` result) into a
+/// [`HealthTracker`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Probe {
+    /// First successful probe after spawn, or a recovery after one or more
+    /// failed probes: the frontend should be told the backend is `ready`.
+    BecameReady,
+    /// Still healthy; nothing changed.
+    StillHealthy,
+    /// Not alive yet, but hasn't failed for long enough to be treated as
+    /// crashed. Covers both the initial startup warm-up (before the first
+    /// successful probe) and a short blip once healthy.
+    StillFailing,
+    /// Was ready at some point, then failed enough consecutive probes in a
+    /// row to be treated as crashed (e.g. a deadlocked process), or never
+    /// became ready before the caller's warm-up period expired.
+    Crashed,
+}
+
+/// Tracks consecutive failed liveness probes for a single sidecar process.
+///
+/// Failures are only counted towards a crash verdict once the process has
+/// become ready at least once — a slow cold start (e.g. `go run` compiling)
+/// isn't a hang, so the warm-up period is left to the caller's own startup
+/// timeout instead of this threshold.
+#[derive(Default)]
+pub struct HealthTracker {
+    became_ready: bool,
+    consecutive_failures: u32,
+}
+
+impl HealthTracker {
+    /// Feeds one liveness probe into the tracker. `warmup_expired` should be
+    /// true once the caller's own startup grace period (e.g. `await_ready`'s
+    /// timeout) has elapsed, so a process that never comes up at all is
+    /// still eventually treated as crashed instead of being watched forever.
+    pub fn record(&mut self, alive: bool, failure_threshold: u32, warmup_expired: bool) -> Probe {
+        if alive {
+            let recovered = !self.became_ready || self.consecutive_failures > 0;
+            self.became_ready = true;
+            self.consecutive_failures = 0;
+            return if recovered {
+                Probe::BecameReady
+            } else {
+                Probe::StillHealthy
+            };
+        }
+
+        if !self.became_ready {
+            return if warmup_expired {
+                Probe::Crashed
+            } else {
+                Probe::StillFailing
+            };
+        }
+
+        self.consecutive_failures += 1;
+        if self.consecutive_failures >= failure_threshold {
+            Probe::Crashed
+        } else {
+            Probe::StillFailing
+        }
+    }
+}
+
+/// Doubles `current`, capped at [`RESTART_BACKOFF_MAX`], for the next
+/// restart attempt.
+pub fn next_backoff(current: Duration) -> Duration {
+    (current * 2).min(RESTART_BACKOFF_MAX)
+}
+
+/// The backoff to use after a sidecar that ran stably (see `STABLE_UPTIME`
+/// in the caller) crashes again.
+pub fn reset_backoff() -> Duration {
+    RESTART_BACKOFF_MIN
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn still_failing_during_warmup() {
+        let mut tracker = HealthTracker::default();
+        assert_eq!(tracker.record(false, 3, false), Probe::StillFailing);
+        assert_eq!(tracker.record(false, 3, false), Probe::StillFailing);
+    }
+
+    #[test]
+    fn crashed_once_warmup_expires_without_ever_becoming_ready() {
+        let mut tracker = HealthTracker::default();
+        assert_eq!(tracker.record(false, 3, false), Probe::StillFailing);
+        assert_eq!(tracker.record(false, 3, true), Probe::Crashed);
+    }
+
+    #[test]
+    fn first_successful_probe_becomes_ready() {
+        let mut tracker = HealthTracker::default();
+        assert_eq!(tracker.record(true, 3, false), Probe::BecameReady);
+    }
+
+    #[test]
+    fn steady_health_after_ready_stays_healthy() {
+        let mut tracker = HealthTracker::default();
+        assert_eq!(tracker.record(true, 3, false), Probe::BecameReady);
+        assert_eq!(tracker.record(true, 3, false), Probe::StillHealthy);
+        assert_eq!(tracker.record(true, 3, false), Probe::StillHealthy);
+    }
+
+    #[test]
+    fn recovers_and_reemits_ready_after_a_failing_streak() {
+        let mut tracker = HealthTracker::default();
+        assert_eq!(tracker.record(true, 3, false), Probe::BecameReady);
+        assert_eq!(tracker.record(false, 3, false), Probe::StillFailing);
+        assert_eq!(tracker.record(true, 3, false), Probe::BecameReady);
+    }
+
+    #[test]
+    fn crashes_once_failure_threshold_is_reached_after_being_ready() {
+        let mut tracker = HealthTracker::default();
+        assert_eq!(tracker.record(true, 3, false), Probe::BecameReady);
+        assert_eq!(tracker.record(false, 3, false), Probe::StillFailing);
+        assert_eq!(tracker.record(false, 3, false), Probe::StillFailing);
+        assert_eq!(tracker.record(false, 3, false), Probe::Crashed);
+    }
+
+    #[test]
+    fn warmup_expiry_does_not_matter_once_already_ready() {
+        let mut tracker = HealthTracker::default();
+        assert_eq!(tracker.record(true, 3, false), Probe::BecameReady);
+        assert_eq!(tracker.record(false, 3, true), Probe::StillFailing);
+    }
+
+    #[test]
+    fn next_backoff_doubles_up_to_the_cap() {
+        let mut backoff = reset_backoff();
+        assert_eq!(backoff, RESTART_BACKOFF_MIN);
+        backoff = next_backoff(backoff);
+        assert_eq!(backoff, RESTART_BACKOFF_MIN * 2);
+        for _ in 0..10 {
+            backoff = next_backoff(backoff);
+        }
+        assert_eq!(backoff, RESTART_BACKOFF_MAX);
+    }
+
+    #[test]
+    fn reset_backoff_returns_the_floor() {
+        assert_eq!(reset_backoff(), RESTART_BACKOFF_MIN);
+    }
+}
@@ -0,0 +1,197 @@
+//! Resolves how to launch the debug backend (`go run ...`) across platforms
+//! and sanitizes the child environment when running inside a desktop
+//! sandbox (Flatpak/Snap/AppImage), where the inherited `PATH`/XDG dirs
+//! point at the bundle rather than the real system.
+
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+const FALLBACK_PATH_DIRS: &[&str] = &[
+    "/usr/local/sbin",
+    "/usr/local/bin",
+    "/usr/sbin",
+    "/usr/bin",
+    "/sbin",
+    "/bin",
+];
+const FALLBACK_DATA_DIRS: &[&str] = &["/usr/local/share", "/usr/share"];
+const FALLBACK_CONFIG_DIRS: &[&str] = &["/etc/xdg"];
+
+/// Prefixes that only exist inside a sandbox's private filesystem view;
+/// stripped from an inherited `PATH`-style value before it's trusted, since
+/// a binary under one of these shadows the real system tool of the same
+/// name.
+const SANDBOX_PATH_PREFIXES: &[&str] = &["/app", "/snap", "/var/lib/flatpak", "/var/lib/snapd"];
+
+/// Which desktop sandbox (if any) the app is currently running inside.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SandboxKind {
+    Flatpak,
+    Snap,
+    AppImage,
+}
+
+fn detect_sandbox() -> Option<SandboxKind> {
+    if matches!(env::var("container"), Ok(v) if v == "flatpak") {
+        return Some(SandboxKind::Flatpak);
+    }
+    if env::var("SNAP").is_ok() {
+        return Some(SandboxKind::Snap);
+    }
+    if env::var("APPIMAGE").is_ok() || env::var("APPDIR").is_ok() {
+        return Some(SandboxKind::AppImage);
+    }
+    None
+}
+
+fn is_sandbox_prefixed(entry: &str) -> bool {
+    SANDBOX_PATH_PREFIXES
+        .iter()
+        .any(|prefix| entry == *prefix || entry.starts_with(&format!("{prefix}/")))
+}
+
+/// Builds a `PATH`-style list with `fallback` dirs first (so the real
+/// system tools win the lookup), followed by whatever's left of `existing`
+/// once sandbox-private prefixes (`/app`, `/snap`, ...) are stripped out.
+/// Drops empty entries and de-duplicates while preserving order so repeated
+/// launches produce an identical result.
+fn merge_path_list(existing: Option<&str>, fallback: &[&str]) -> String {
+    let mut seen = std::collections::HashSet::new();
+    let mut merged = Vec::new();
+    let filtered_existing = existing
+        .unwrap_or_default()
+        .split(':')
+        .filter(|entry| !is_sandbox_prefixed(entry));
+    for entry in fallback.iter().copied().chain(filtered_existing) {
+        if entry.is_empty() || !seen.insert(entry) {
+            continue;
+        }
+        merged.push(entry);
+    }
+    merged.join(":")
+}
+
+/// The `PATH` to use for both resolving the `go` binary and launching the
+/// sidecar: the real system dirs ahead of whatever's left of the inherited
+/// `PATH` when running inside a sandbox, or the inherited `PATH` unchanged
+/// otherwise.
+fn effective_path() -> String {
+    let inherited = env::var("PATH").ok();
+    if detect_sandbox().is_none() {
+        return inherited.unwrap_or_default();
+    }
+    merge_path_list(inherited.as_deref(), FALLBACK_PATH_DIRS)
+}
+
+/// Finds the `go` executable by searching `effective_path()` rather than
+/// the process's raw (possibly sandbox-polluted) `PATH`, independent of any
+/// login shell.
+pub fn resolve_go() -> Option<PathBuf> {
+    let cwd = env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    which::which_in("go", Some(effective_path()), cwd).ok()
+}
+
+/// Builds a command that runs `go run ./cmd/agenterm <args>` via a resolved
+/// `go` binary, falling back to a platform-appropriate login shell only when
+/// `go` can't be found directly on `PATH`.
+pub fn go_run_command(args: &[String]) -> Command {
+    if let Some(go) = resolve_go() {
+        let mut cmd = Command::new(go);
+        cmd.arg("run").arg("./cmd/agenterm").args(args);
+        return cmd;
+    }
+
+    let invocation = format!("go run ./cmd/agenterm {}", args.join(" "));
+    login_shell_command(&invocation)
+}
+
+#[cfg(windows)]
+fn login_shell_command(invocation: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(invocation);
+    cmd
+}
+
+#[cfg(not(windows))]
+fn login_shell_command(invocation: &str) -> Command {
+    let shell = env::var("SHELL").unwrap_or_else(|_| String::from("/bin/sh"));
+    let mut cmd = Command::new(shell);
+    cmd.arg("-lc").arg(invocation);
+    cmd
+}
+
+/// Rebuilds `PATH`, `XDG_DATA_DIRS`, and `XDG_CONFIG_DIRS` on `cmd` with the
+/// real system locations when the app is running inside a sandbox, so the
+/// sidecar sees the host's tools instead of the bundle's injected paths. A
+/// no-op outside a detected sandbox.
+pub fn normalize_sandbox_env(cmd: &mut Command) {
+    if detect_sandbox().is_none() {
+        return;
+    }
+
+    cmd.env("PATH", effective_path());
+    cmd.env(
+        "XDG_DATA_DIRS",
+        merge_path_list(
+            env::var("XDG_DATA_DIRS").ok().as_deref(),
+            FALLBACK_DATA_DIRS,
+        ),
+    );
+    cmd.env(
+        "XDG_CONFIG_DIRS",
+        merge_path_list(
+            env::var("XDG_CONFIG_DIRS").ok().as_deref(),
+            FALLBACK_CONFIG_DIRS,
+        ),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_sandbox_prefixed_matches_known_prefixes() {
+        assert!(is_sandbox_prefixed("/app/bin"));
+        assert!(is_sandbox_prefixed("/snap/bin"));
+        assert!(is_sandbox_prefixed("/var/lib/flatpak/exports/bin"));
+        assert!(is_sandbox_prefixed("/var/lib/snapd/snap/bin"));
+        assert!(!is_sandbox_prefixed("/usr/bin"));
+        assert!(!is_sandbox_prefixed("/app-data/bin"));
+    }
+
+    #[test]
+    fn merge_path_list_puts_fallback_dirs_first() {
+        let merged = merge_path_list(Some("/usr/bin:/usr/local/bin"), FALLBACK_PATH_DIRS);
+        let fallback_end = FALLBACK_PATH_DIRS.len();
+        assert_eq!(
+            &merged.split(':').take(fallback_end).collect::<Vec<_>>(),
+            FALLBACK_PATH_DIRS
+        );
+    }
+
+    #[test]
+    fn merge_path_list_strips_sandbox_prefixed_entries() {
+        let merged = merge_path_list(Some("/app/bin:/usr/bin:/snap/bin"), FALLBACK_PATH_DIRS);
+        assert!(!merged.split(':').any(is_sandbox_prefixed));
+        assert!(merged.split(':').any(|e| e == "/usr/bin"));
+    }
+
+    #[test]
+    fn merge_path_list_drops_empty_entries_and_dedupes() {
+        let merged = merge_path_list(Some("::/usr/local/bin:/usr/bin:"), FALLBACK_PATH_DIRS);
+        let entries: Vec<&str> = merged.split(':').collect();
+        assert!(!entries.iter().any(|e| e.is_empty()));
+        let mut seen = std::collections::HashSet::new();
+        assert!(entries.iter().all(|e| seen.insert(*e)));
+    }
+
+    #[test]
+    fn merge_path_list_is_deterministic_across_repeated_launches() {
+        let input = Some("/app/bin:/usr/bin:/usr/local/bin");
+        let first = merge_path_list(input, FALLBACK_PATH_DIRS);
+        let second = merge_path_list(input, FALLBACK_PATH_DIRS);
+        assert_eq!(first, second);
+    }
+}
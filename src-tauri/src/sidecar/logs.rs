@@ -0,0 +1,104 @@
+//! Tees the sidecar's stdout/stderr to the on-disk log file, an in-memory
+//! ring buffer, and `backend://log` events so the frontend can show live
+//! logs and backfill history for a freshly opened panel.
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tauri::{AppHandle, Emitter};
+
+const RING_BUFFER_CAPACITY: usize = 2000;
+
+#[derive(Clone, Serialize)]
+pub struct LogLine {
+    stream: &'static str,
+    line: String,
+    ts: u64,
+}
+
+static RING_BUFFER: OnceLock<Mutex<VecDeque<LogLine>>> = OnceLock::new();
+
+fn ring_buffer() -> &'static Mutex<VecDeque<LogLine>> {
+    RING_BUFFER.get_or_init(|| Mutex::new(VecDeque::with_capacity(RING_BUFFER_CAPACITY)))
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn record(line: LogLine) {
+    if let Ok(mut buf) = ring_buffer().lock() {
+        if buf.len() == RING_BUFFER_CAPACITY {
+            buf.pop_front();
+        }
+        buf.push_back(line);
+    }
+}
+
+fn spawn_reader<R: Read + Send + 'static>(
+    app: AppHandle,
+    stream: &'static str,
+    reader: R,
+    log_file: Arc<Mutex<File>>,
+) {
+    thread::spawn(move || {
+        for line in BufReader::new(reader).lines() {
+            let Ok(line) = line else { break };
+            if let Ok(mut file) = log_file.lock() {
+                let _ = writeln!(file, "[{stream}] {line}");
+            }
+            let entry = LogLine {
+                stream,
+                line,
+                ts: now_millis(),
+            };
+            record(entry.clone());
+            if let Err(err) = app.emit("backend://log", entry) {
+                eprintln!("[agenTerm] failed to emit backend://log: {err}");
+            }
+        }
+    });
+}
+
+/// Starts tee'ing `stdout`/`stderr` to `log_path`, the ring buffer, and
+/// `backend://log` events. Spawns one reader thread per stream.
+pub fn start_capture(
+    app: AppHandle,
+    stdout: impl Read + Send + 'static,
+    stderr: impl Read + Send + 'static,
+    log_path: &Path,
+) -> Result<(), String> {
+    let log_file = File::options()
+        .create(true)
+        .append(true)
+        .open(log_path)
+        .map_err(|e| format!("open sidecar log {}: {}", log_path.display(), e))?;
+    let log_file = Arc::new(Mutex::new(log_file));
+
+    spawn_reader(app.clone(), "stdout", stdout, log_file.clone());
+    spawn_reader(app, "stderr", stderr, log_file);
+    Ok(())
+}
+
+/// Returns the last `lines` buffered log entries, oldest first, so a
+/// freshly opened log panel can backfill history before live events start
+/// flowing.
+#[tauri::command]
+pub fn backend_log_tail(lines: usize) -> Vec<LogLine> {
+    ring_buffer()
+        .lock()
+        .map(|buf| {
+            let skip = buf.len().saturating_sub(lines);
+            buf.iter().skip(skip).cloned().collect()
+        })
+        .unwrap_or_default()
+}
@@ -0,0 +1,374 @@
+//! Lifecycle management for the Go backend sidecar: spawning, health
+//! monitoring with auto-restart, and shutdown.
+
+use serde::Serialize;
+use std::fs;
+use std::net::{SocketAddr, TcpStream};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use shared_child::SharedChild;
+use tauri::{AppHandle, Emitter};
+
+mod health;
+mod launcher;
+mod logs;
+mod runtime;
+mod shutdown;
+
+use health::{HealthTracker, Probe};
+pub use logs::backend_log_tail;
+use runtime::SidecarRuntime;
+
+const BACKEND_HOST: &str = "127.0.0.1";
+const BACKEND_DB_PATH: &str = ".cache/desktop/agenterm.db";
+const BACKEND_AGENTS_DIR: &str = "configs/agents";
+const BACKEND_PLAYBOOKS_DIR: &str = "configs/playbooks";
+
+/// Backend state broadcast to the frontend on the `backend://state` event.
+const STATE_STARTING: &str = "starting";
+const STATE_READY: &str = "ready";
+const STATE_CRASHED: &str = "crashed";
+const STATE_RESTARTING: &str = "restarting";
+
+pub(crate) const RESTART_BACKOFF_MIN: Duration = Duration::from_millis(300);
+pub(crate) const RESTART_BACKOFF_MAX: Duration = Duration::from_secs(5);
+/// A sidecar that stays up for at least this long is considered stable and
+/// resets the backoff back to `RESTART_BACKOFF_MIN` on its next crash.
+const STABLE_UPTIME: Duration = Duration::from_secs(60);
+const MONITOR_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Consecutive failed `backend_alive()` probes before a still-running but
+/// unresponsive (hung) sidecar is treated as crashed.
+const HEALTH_FAILURE_THRESHOLD: u32 = 3;
+/// How long a freshly spawned sidecar gets to start accepting connections
+/// before a cold start that never finishes is treated as crashed.
+const AWAIT_READY_TIMEOUT: Duration = Duration::from_secs(10);
+
+static BACKEND_CHILD: OnceLock<Mutex<Option<Arc<SharedChild>>>> = OnceLock::new();
+static SHUTTING_DOWN: AtomicBool = AtomicBool::new(false);
+static MONITOR_STARTED: OnceLock<()> = OnceLock::new();
+
+#[derive(Clone, Serialize)]
+struct BackendStateEvent {
+    state: &'static str,
+}
+
+fn emit_state(app: &AppHandle, state: &'static str) {
+    if let Err(err) = app.emit("backend://state", BackendStateEvent { state }) {
+        eprintln!("[agenTerm] failed to emit backend://state({state}): {err}");
+    }
+}
+
+#[derive(Serialize)]
+struct DesktopRuntimeInfo {
+    platform: &'static str,
+    app: &'static str,
+    backend_url: String,
+    backend_token: String,
+    sidecar_managed: bool,
+}
+
+fn project_root() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("..")
+}
+
+fn backend_addr(port: u16) -> SocketAddr {
+    format!("{}:{}", BACKEND_HOST, port)
+        .parse()
+        .unwrap_or_else(|_| SocketAddr::from(([127, 0, 0, 1], port)))
+}
+
+fn backend_alive(port: u16) -> bool {
+    TcpStream::connect_timeout(&backend_addr(port), Duration::from_millis(300)).is_ok()
+}
+
+fn sidecar_disabled() -> bool {
+    matches!(std::env::var("AGENTERM_NO_SIDECAR"), Ok(v) if v == "1" || v.eq_ignore_ascii_case("true"))
+}
+
+fn backend_child_lock() -> &'static Mutex<Option<Arc<SharedChild>>> {
+    BACKEND_CHILD.get_or_init(|| Mutex::new(None))
+}
+
+fn build_sidecar_command(root: &PathBuf, runtime: &SidecarRuntime) -> Result<Command, String> {
+    let mut cmd = if cfg!(debug_assertions) {
+        launcher::go_run_command(&[
+            "--port".into(),
+            runtime.port.to_string(),
+            "--token".into(),
+            runtime.token.clone(),
+            "--db-path".into(),
+            BACKEND_DB_PATH.into(),
+            "--agents-dir".into(),
+            BACKEND_AGENTS_DIR.into(),
+            "--playbooks-dir".into(),
+            BACKEND_PLAYBOOKS_DIR.into(),
+            "--dir".into(),
+            ".".into(),
+        ])
+    } else {
+        let exe = std::env::current_exe().map_err(|e| format!("resolve current exe: {}", e))?;
+        let exe_dir = exe
+            .parent()
+            .ok_or_else(|| String::from("resolve executable directory"))?;
+        let candidates = [exe_dir.join("agenterm"), exe_dir.join("agenterm-server")];
+        let binary = candidates
+            .iter()
+            .find(|candidate| candidate.exists())
+            .ok_or_else(|| String::from("desktop backend binary not found near app executable"))?;
+        Command::new(binary)
+    };
+
+    cmd.current_dir(root);
+    if !cfg!(debug_assertions) {
+        cmd.arg("--port")
+            .arg(runtime.port.to_string())
+            .arg("--token")
+            .arg(&runtime.token)
+            .arg("--db-path")
+            .arg(BACKEND_DB_PATH)
+            .arg("--agents-dir")
+            .arg(BACKEND_AGENTS_DIR)
+            .arg("--playbooks-dir")
+            .arg(BACKEND_PLAYBOOKS_DIR)
+            .arg("--dir")
+            .arg(".");
+    }
+
+    cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+
+    launcher::normalize_sandbox_env(&mut cmd);
+    Ok(cmd)
+}
+
+/// Spawns the sidecar if it isn't already running. Returns `Ok(true)` if a
+/// new process was started.
+fn spawn_backend_sidecar(app: &AppHandle) -> Result<bool, String> {
+    let runtime = runtime::get_or_init()?;
+    if sidecar_disabled() || backend_alive(runtime.port) {
+        return Ok(false);
+    }
+    let root = project_root();
+    let cache_dir = root.join(".cache").join("desktop");
+    fs::create_dir_all(&cache_dir)
+        .map_err(|e| format!("create desktop cache directory {}: {}", cache_dir.display(), e))?;
+
+    let lock = backend_child_lock();
+    let mut guard = lock
+        .lock()
+        .map_err(|_| String::from("backend sidecar lock poisoned"))?;
+    if guard.is_some() {
+        return Ok(false);
+    }
+
+    let mut cmd = build_sidecar_command(&root, &runtime)?;
+    let mut std_child = cmd
+        .spawn()
+        .map_err(|e| format!("spawn backend sidecar failed: {}", e))?;
+
+    if let (Some(stdout), Some(stderr)) = (std_child.stdout.take(), std_child.stderr.take()) {
+        let log_path = cache_dir.join("backend-sidecar.log");
+        logs::start_capture(app.clone(), stdout, stderr, &log_path)?;
+    }
+
+    let child = SharedChild::new(std_child)
+        .map_err(|e| format!("wrap backend sidecar child: {}", e))?;
+
+    thread::sleep(Duration::from_millis(600));
+    if let Ok(Some(status)) = child.try_wait() {
+        return Err(format!(
+            "backend sidecar exited early with status {}",
+            status
+        ));
+    }
+
+    *guard = Some(Arc::new(child));
+    Ok(true)
+}
+
+fn stop_backend_sidecar() {
+    SHUTTING_DOWN.store(true, Ordering::SeqCst);
+    if let Some(lock) = BACKEND_CHILD.get() {
+        if let Ok(mut guard) = lock.lock() {
+            if let Some(child) = guard.take() {
+                match runtime::get_or_init() {
+                    Ok(runtime) => shutdown::stop(&child, runtime.port, &runtime.token),
+                    Err(_) => {
+                        let _ = child.kill();
+                        let _ = child.wait();
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Waits for the sidecar to start accepting connections, emitting `ready`
+/// once it does or `crashed` if it exits before coming up or doesn't come up
+/// within `AWAIT_READY_TIMEOUT`.
+fn await_ready(app: &AppHandle, child: &Arc<SharedChild>, port: u16) {
+    let deadline = Instant::now() + AWAIT_READY_TIMEOUT;
+    while Instant::now() < deadline {
+        if SHUTTING_DOWN.load(Ordering::SeqCst) {
+            return;
+        }
+        if backend_alive(port) {
+            emit_state(app, STATE_READY);
+            return;
+        }
+        if matches!(child.try_wait(), Ok(Some(_))) {
+            emit_state(app, STATE_CRASHED);
+            return;
+        }
+        thread::sleep(MONITOR_POLL_INTERVAL);
+    }
+    emit_state(app, STATE_CRASHED);
+}
+
+/// Background monitor that watches the sidecar, escalating a dead process
+/// into a respawn with capped exponential backoff.
+fn monitor_loop(app: AppHandle) {
+    let mut backoff = health::reset_backoff();
+    loop {
+        if SHUTTING_DOWN.load(Ordering::SeqCst) {
+            return;
+        }
+
+        let child = backend_child_lock().lock().ok().and_then(|g| g.clone());
+        let Some(child) = child else {
+            thread::sleep(MONITOR_POLL_INTERVAL);
+            continue;
+        };
+
+        let started_at = Instant::now();
+        let port = runtime::get_or_init().ok().map(|r| r.port);
+        let mut tracker = HealthTracker::default();
+        loop {
+            if SHUTTING_DOWN.load(Ordering::SeqCst) {
+                return;
+            }
+            match child.try_wait() {
+                Ok(Some(_)) => break,
+                Err(_) => break,
+                Ok(None) => {}
+            }
+            let Some(port) = port else {
+                thread::sleep(MONITOR_POLL_INTERVAL);
+                continue;
+            };
+            let warmup_expired = started_at.elapsed() >= AWAIT_READY_TIMEOUT;
+            match tracker.record(backend_alive(port), HEALTH_FAILURE_THRESHOLD, warmup_expired) {
+                Probe::BecameReady => emit_state(&app, STATE_READY),
+                Probe::StillHealthy | Probe::StillFailing => {}
+                Probe::Crashed => {
+                    // The process is still running but stopped answering
+                    // health checks (e.g. deadlocked): kill it so the port
+                    // frees up for the respawn below.
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    break;
+                }
+            }
+            thread::sleep(MONITOR_POLL_INTERVAL);
+        }
+        if SHUTTING_DOWN.load(Ordering::SeqCst) {
+            return;
+        }
+
+        // The sidecar exited without us asking it to (or was killed above
+        // for failing its health checks): clear the slot and
+        // respawn, backing off harder the more rapidly it keeps crashing.
+        if let Ok(mut guard) = backend_child_lock().lock() {
+            guard.take();
+        }
+        emit_state(&app, STATE_CRASHED);
+
+        if started_at.elapsed() >= STABLE_UPTIME {
+            backoff = health::reset_backoff();
+        }
+
+        emit_state(&app, STATE_RESTARTING);
+        thread::sleep(backoff);
+        backoff = health::next_backoff(backoff);
+
+        if SHUTTING_DOWN.load(Ordering::SeqCst) || sidecar_disabled() {
+            return;
+        }
+        match spawn_backend_sidecar(&app) {
+            Ok(true) => {
+                emit_state(&app, STATE_STARTING);
+                if let (Some(child), Ok(runtime)) = (
+                    backend_child_lock().lock().ok().and_then(|g| g.clone()),
+                    runtime::get_or_init(),
+                ) {
+                    await_ready(&app, &child, runtime.port);
+                }
+            }
+            Ok(false) => {}
+            Err(err) => eprintln!("[agenTerm] backend sidecar respawn failed: {err}"),
+        }
+    }
+}
+
+/// Starts the sidecar (if enabled) and the background health monitor that
+/// respawns it on unexpected exit. Once the monitor has been started, later
+/// calls are no-ops, even after the monitor thread has exited.
+pub fn start(app: &AppHandle) {
+    if sidecar_disabled() {
+        return;
+    }
+    if MONITOR_STARTED.set(()).is_err() {
+        return;
+    }
+
+    emit_state(app, STATE_STARTING);
+    match spawn_backend_sidecar(app) {
+        Ok(true) => {
+            if let (Some(child), Ok(runtime)) = (
+                backend_child_lock().lock().ok().and_then(|g| g.clone()),
+                runtime::get_or_init(),
+            ) {
+                await_ready(app, &child, runtime.port);
+            }
+        }
+        Ok(false) => {
+            if let Ok(runtime) = runtime::get_or_init() {
+                if backend_alive(runtime.port) {
+                    emit_state(app, STATE_READY);
+                }
+            }
+        }
+        Err(err) => {
+            eprintln!("[agenTerm] backend sidecar startup warning: {err}");
+            emit_state(app, STATE_CRASHED);
+        }
+    }
+
+    let monitor_app = app.clone();
+    thread::spawn(move || monitor_loop(monitor_app));
+}
+
+/// Stops the monitor and any running sidecar. Called on app shutdown.
+pub fn stop() {
+    stop_backend_sidecar();
+}
+
+#[tauri::command]
+pub fn desktop_runtime_info() -> Result<DesktopRuntimeInfo, String> {
+    let runtime = runtime::get_or_init()?;
+    let sidecar_managed = BACKEND_CHILD
+        .get()
+        .and_then(|lock| lock.lock().ok().map(|g| g.is_some()))
+        .unwrap_or(false);
+    Ok(DesktopRuntimeInfo {
+        platform: std::env::consts::OS,
+        app: "agenTerm",
+        backend_url: format!("http://{}:{}", BACKEND_HOST, runtime.port),
+        backend_token: runtime.token,
+        sidecar_managed,
+    })
+}
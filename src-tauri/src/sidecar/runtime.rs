@@ -0,0 +1,82 @@
+//! Per-launch sidecar runtime parameters: an ephemeral loopback port and a
+//! fresh high-entropy token, chosen once and shared by every command that
+//! needs to talk to (or about) the backend.
+
+use rand::RngCore;
+use std::net::TcpListener;
+use std::sync::{Mutex, OnceLock};
+
+const TOKEN_BYTES: usize = 32;
+
+#[derive(Clone)]
+pub struct SidecarRuntime {
+    pub port: u16,
+    pub token: String,
+}
+
+static RUNTIME: OnceLock<Mutex<Option<SidecarRuntime>>> = OnceLock::new();
+
+fn allocate_port() -> Result<u16, String> {
+    let listener = TcpListener::bind(("127.0.0.1", 0))
+        .map_err(|e| format!("bind ephemeral sidecar port: {}", e))?;
+    let port = listener
+        .local_addr()
+        .map_err(|e| format!("read ephemeral sidecar port: {}", e))?
+        .port();
+    // Drop the listener so the port is free again before the sidecar binds it.
+    drop(listener);
+    Ok(port)
+}
+
+fn generate_token() -> String {
+    let mut bytes = [0u8; TOKEN_BYTES];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Returns this launch's `(port, token)`, allocating them on first call.
+pub fn get_or_init() -> Result<SidecarRuntime, String> {
+    let lock = RUNTIME.get_or_init(|| Mutex::new(None));
+    let mut guard = lock
+        .lock()
+        .map_err(|_| String::from("sidecar runtime lock poisoned"))?;
+    if let Some(runtime) = guard.as_ref() {
+        return Ok(runtime.clone());
+    }
+    let runtime = SidecarRuntime {
+        port: allocate_port()?,
+        token: generate_token(),
+    };
+    *guard = Some(runtime.clone());
+    Ok(runtime)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_token_is_64_lowercase_hex_chars() {
+        let token = generate_token();
+        assert_eq!(token.len(), TOKEN_BYTES * 2);
+        assert!(token.chars().all(|c| c.is_ascii_hexdigit() && !c.is_ascii_uppercase()));
+    }
+
+    #[test]
+    fn generate_token_is_fresh_each_call() {
+        assert_ne!(generate_token(), generate_token());
+    }
+
+    #[test]
+    fn allocate_port_returns_a_nonzero_loopback_port() {
+        let port = allocate_port().expect("port allocation should succeed");
+        assert_ne!(port, 0);
+    }
+
+    #[test]
+    fn allocate_port_frees_the_port_before_returning() {
+        let port = allocate_port().expect("port allocation should succeed");
+        TcpListener::bind(("127.0.0.1", port))
+            .expect("allocated port should be free to rebind immediately");
+    }
+}
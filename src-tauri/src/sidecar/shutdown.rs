@@ -0,0 +1,74 @@
+//! Orderly shutdown sequence for the sidecar: ask it to stop first, give it
+//! a bounded grace period to flush state (its SQLite database in
+//! particular), and only escalate to a hard kill if it doesn't exit in
+//! time.
+
+use std::io::Write;
+use std::net::TcpStream;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use shared_child::SharedChild;
+#[cfg(unix)]
+use shared_child::unix::SharedChildExt;
+
+const DEFAULT_SHUTDOWN_TIMEOUT_MS: u64 = 3000;
+const SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+#[cfg(unix)]
+const SIGTERM: i32 = 15;
+
+fn shutdown_timeout() -> Duration {
+    std::env::var("AGENTERM_SIDECAR_SHUTDOWN_MS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .map(Duration::from_millis)
+        .unwrap_or(Duration::from_millis(DEFAULT_SHUTDOWN_TIMEOUT_MS))
+}
+
+/// Sends a `POST /shutdown` request over the sidecar's loopback connection,
+/// authenticated with the session token. Best-effort: the backend may
+/// already be gone, or may close the connection without replying once it
+/// starts tearing down.
+fn post_shutdown(port: u16, token: &str) {
+    let Ok(mut stream) = TcpStream::connect(("127.0.0.1", port)) else {
+        return;
+    };
+    let _ = stream.set_write_timeout(Some(Duration::from_millis(500)));
+    let request = format!(
+        "POST /shutdown HTTP/1.1\r\nHost: 127.0.0.1:{port}\r\nAuthorization: Bearer {token}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+    );
+    let _ = stream.write_all(request.as_bytes());
+}
+
+#[cfg(unix)]
+fn send_sigterm(child: &SharedChild) {
+    let _ = child.send_signal(SIGTERM);
+}
+
+#[cfg(not(unix))]
+fn send_sigterm(_child: &SharedChild) {}
+
+/// Attempts an orderly stop of `child` (an HTTP shutdown request, plus
+/// SIGTERM on Unix), waits up to `AGENTERM_SIDECAR_SHUTDOWN_MS` (default
+/// 3000ms) for it to exit on its own, and only force-kills it if it's still
+/// alive afterwards.
+pub fn stop(child: &Arc<SharedChild>, port: u16, token: &str) {
+    if matches!(child.try_wait(), Ok(Some(_))) {
+        return;
+    }
+
+    post_shutdown(port, token);
+    send_sigterm(child);
+
+    let deadline = Instant::now() + shutdown_timeout();
+    while Instant::now() < deadline {
+        if matches!(child.try_wait(), Ok(Some(_))) {
+            return;
+        }
+        thread::sleep(SHUTDOWN_POLL_INTERVAL);
+    }
+
+    let _ = child.kill();
+    let _ = child.wait();
+}